@@ -0,0 +1,446 @@
+//! Hashlife: advances the universe via a hashconsed quadtree instead of
+//! recomputing every cell every generation, the way `Grid::next` does.
+
+use crate::Position;
+use hashbrown::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// A square region of the universe. Levels 0 and 1 are folded together into a
+/// `Leaf`: a level-1 (2x2) node storing its four cells directly as bits
+/// (bit 0 = nw, 1 = ne, 2 = sw, 3 = se) rather than as pointers, since
+/// allocating a `Node` per single cell would dwarf the cells themselves.
+/// Level `k >= 2` nodes cover a `2^k x 2^k` region and are split into four
+/// `2^(k-1) x 2^(k-1)` children.
+#[derive(Debug)]
+pub enum Node {
+    Leaf {
+        bits: u8,
+    },
+    Interior {
+        level: u8,
+        nw: Arc<Node>,
+        ne: Arc<Node>,
+        sw: Arc<Node>,
+        se: Arc<Node>,
+        population: u64,
+    },
+}
+
+impl Node {
+    fn level(&self) -> u8 {
+        match self {
+            Node::Leaf { .. } => 1,
+            Node::Interior { level, .. } => *level,
+        }
+    }
+
+    fn population(&self) -> u64 {
+        match self {
+            Node::Leaf { bits } => bits.count_ones() as u64,
+            Node::Interior { population, .. } => *population,
+        }
+    }
+
+    fn children(&self) -> (Arc<Node>, Arc<Node>, Arc<Node>, Arc<Node>) {
+        match self {
+            Node::Interior { nw, ne, sw, se, .. } => (nw.clone(), ne.clone(), sw.clone(), se.clone()),
+            Node::Leaf { .. } => unreachable!("a leaf has no quadtree children"),
+        }
+    }
+}
+
+/// Shallow, O(1) identity for the canonicalization table: two interior nodes
+/// are the same node iff they're the same level built from the same (already
+/// canonical) children, so we only ever compare pointers, never recurse.
+#[derive(PartialEq, Eq, Hash)]
+enum NodeKey {
+    Leaf(u8),
+    Interior(u8, usize, usize, usize, usize),
+}
+
+fn interior_key(level: u8, nw: &Arc<Node>, ne: &Arc<Node>, sw: &Arc<Node>, se: &Arc<Node>) -> NodeKey {
+    NodeKey::Interior(
+        level,
+        Arc::as_ptr(nw) as usize,
+        Arc::as_ptr(ne) as usize,
+        Arc::as_ptr(sw) as usize,
+        Arc::as_ptr(se) as usize,
+    )
+}
+
+/// Wraps an `Arc<Node>` so the results cache can key on node *identity*
+/// (pointer equality) instead of structural equality: since nodes are
+/// hashconsed, identical content always means the identical `Arc`, so
+/// identity hashing is both correct and avoids ever walking a subtree just to
+/// look up its cached result.
+#[derive(Clone)]
+struct NodeRef(Arc<Node>);
+
+impl PartialEq for NodeRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for NodeRef {}
+
+impl Hash for NodeRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as usize).hash(state);
+    }
+}
+
+/// Alternative simulation engine to `Grid`. Exploits the same
+/// spatial/temporal redundancy Gosper's Hashlife algorithm targets: identical
+/// subtrees are shared via `nodes`, and `result` memoizes "this subtree,
+/// advanced in time" via `results` so that recurring structures are only ever
+/// simulated once no matter how many times they reappear.
+pub struct HashlifeGrid {
+    root: Arc<Node>,
+    level: u8,
+    /// World-space coordinates of the root's nw corner, so `to_cells` can undo the
+    /// shift `from_cells` applied when placing the bounding box inside the quadtree.
+    origin_x: i32,
+    origin_y: i32,
+    nodes: HashMap<NodeKey, Arc<Node>>,
+    results: HashMap<NodeRef, Arc<Node>>,
+    empties: Vec<Option<Arc<Node>>>,
+}
+
+impl HashlifeGrid {
+    /// Builds a hashlife universe from the same sparse representation `Grid` uses.
+    /// As in `Grid::next`, only cells of age 1 count as alive; older entries are the
+    /// fading trail of dead cells and are left out.
+    pub fn from_cells(cells: &std::collections::HashMap<Position, u32>) -> HashlifeGrid {
+        let live: Vec<&Position> = cells.iter().filter(|(_, &age)| age == 1).map(|(pos, _)| pos).collect();
+        let mut engine = HashlifeGrid {
+            root: Arc::new(Node::Leaf { bits: 0 }),
+            level: 1,
+            origin_x: 0,
+            origin_y: 0,
+            nodes: HashMap::new(),
+            results: HashMap::new(),
+            empties: Vec::new(),
+        };
+
+        if live.is_empty() {
+            return engine;
+        }
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+        for pos in &live {
+            min_x = min_x.min(pos.x);
+            min_y = min_y.min(pos.y);
+            max_x = max_x.max(pos.x);
+            max_y = max_y.max(pos.y);
+        }
+
+        let span = (max_x - min_x + 1).max(max_y - min_y + 1).max(1) as u32;
+        let mut level = 1u8;
+        while (1u32 << level) < span {
+            level += 1;
+        }
+        // Extra headroom so the first `step()` has empty border to expand into.
+        level = level.max(3) + 2;
+
+        let size = 1i32 << level;
+        let origin_x = min_x - (size - (max_x - min_x + 1)) / 2;
+        let origin_y = min_y - (size - (max_y - min_y + 1)) / 2;
+        engine.level = level;
+        engine.origin_x = origin_x;
+        engine.origin_y = origin_y;
+        engine.root = engine.empty(level);
+        for pos in &live {
+            engine.set_alive(pos.x - origin_x, pos.y - origin_y);
+        }
+        engine
+    }
+
+    /// Flattens the quadtree back into the sparse representation `Grid::cells` uses.
+    /// All returned cells are reported with age 1; hashlife does not track cell age.
+    pub fn to_cells(&self) -> std::collections::HashMap<Position, u32> {
+        let mut out = std::collections::HashMap::new();
+        Self::collect(&self.root, self.origin_x, self.origin_y, &mut out);
+        out
+    }
+
+    fn collect(node: &Arc<Node>, x: i32, y: i32, out: &mut std::collections::HashMap<Position, u32>) {
+        if node.population() == 0 {
+            return;
+        }
+        match node.as_ref() {
+            Node::Leaf { bits } => {
+                for (i, (dx, dy)) in [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().enumerate() {
+                    if bits & (1 << i) != 0 {
+                        out.insert(Position { x: x + dx, y: y + dy }, 1);
+                    }
+                }
+            }
+            Node::Interior { level, nw, ne, sw, se, .. } => {
+                let half = 1i32 << (level - 1);
+                Self::collect(nw, x, y, out);
+                Self::collect(ne, x + half, y, out);
+                Self::collect(sw, x, y + half, out);
+                Self::collect(se, x + half, y + half, out);
+            }
+        }
+    }
+
+    /// Advances the whole universe one hashlife step: pads the root with an
+    /// empty border so it can "expand," then recursively computes `result`.
+    /// Note this is the defining hashlife trade-off: one `step()` call
+    /// advances time by `2^level` generations, `level` being the root's level
+    /// before the call (a growing jump, as each call leaves the tree one level
+    /// taller), not a fixed single generation.
+    pub fn step(&mut self) {
+        self.expand();
+        self.expand();
+        let new_root = self.result(self.root.clone());
+        let quarter = 1i32 << (self.level - 2);
+        self.origin_x += quarter;
+        self.origin_y += quarter;
+        self.root = new_root;
+        self.level -= 1;
+    }
+
+    /// Doubles the universe, re-centering the current root in the middle of
+    /// the new (empty-bordered) root so `result` always has live margin to work with.
+    fn expand(&mut self) {
+        let e = self.empty(self.level - 1);
+        let (a, b, c, d) = self.root.children();
+        let nw = self.combine4(e.clone(), e.clone(), e.clone(), a);
+        let ne = self.combine4(e.clone(), e.clone(), b, e.clone());
+        let sw = self.combine4(e.clone(), c, e.clone(), e.clone());
+        let se = self.combine4(d, e.clone(), e.clone(), e);
+        self.root = self.combine4(nw, ne, sw, se);
+        let half_old = 1i32 << (self.level - 1);
+        self.origin_x -= half_old;
+        self.origin_y -= half_old;
+        self.level += 1;
+    }
+
+    fn empty(&mut self, level: u8) -> Arc<Node> {
+        if level == 1 {
+            return self.make_leaf(0);
+        }
+        let idx = level as usize;
+        if let Some(Some(node)) = self.empties.get(idx) {
+            return node.clone();
+        }
+        let child = self.empty(level - 1);
+        let node = self.combine4(child.clone(), child.clone(), child.clone(), child);
+        if self.empties.len() <= idx {
+            self.empties.resize(idx + 1, None);
+        }
+        self.empties[idx] = Some(node.clone());
+        node
+    }
+
+    fn make_leaf(&mut self, bits: u8) -> Arc<Node> {
+        let key = NodeKey::Leaf(bits);
+        if let Some(existing) = self.nodes.get(&key) {
+            return existing.clone();
+        }
+        let node = Arc::new(Node::Leaf { bits });
+        self.nodes.insert(key, node.clone());
+        node
+    }
+
+    fn combine4(&mut self, nw: Arc<Node>, ne: Arc<Node>, sw: Arc<Node>, se: Arc<Node>) -> Arc<Node> {
+        let level = nw.level() + 1;
+        let key = interior_key(level, &nw, &ne, &sw, &se);
+        if let Some(existing) = self.nodes.get(&key) {
+            return existing.clone();
+        }
+        let population = nw.population() + ne.population() + sw.population() + se.population();
+        let node = Arc::new(Node::Interior { level, nw, ne, sw, se, population });
+        self.nodes.insert(key, node.clone());
+        node
+    }
+
+    /// Sets the cell at `(x, y)` (in node-local coordinates, origin at the root's nw corner) alive.
+    fn set_alive(&mut self, x: i32, y: i32) {
+        let root = self.root.clone();
+        self.root = self.set_alive_in(root, self.level, x, y);
+    }
+
+    fn set_alive_in(&mut self, node: Arc<Node>, level: u8, x: i32, y: i32) -> Arc<Node> {
+        if x < 0 || y < 0 || x >= (1i32 << level) || y >= (1i32 << level) {
+            return node;
+        }
+        if level == 1 {
+            let bits = match node.as_ref() {
+                Node::Leaf { bits } => *bits,
+                Node::Interior { .. } => unreachable!("level-1 node is always a Leaf"),
+            };
+            let idx = (x + 2 * y) as u8;
+            return self.make_leaf(bits | (1 << idx));
+        }
+        let (nw, ne, sw, se) = node.children();
+        let half = 1i32 << (level - 1);
+        if x < half && y < half {
+            let nw = self.set_alive_in(nw, level - 1, x, y);
+            self.combine4(nw, ne, sw, se)
+        } else if x >= half && y < half {
+            let ne = self.set_alive_in(ne, level - 1, x - half, y);
+            self.combine4(nw, ne, sw, se)
+        } else if x < half && y >= half {
+            let sw = self.set_alive_in(sw, level - 1, x, y - half);
+            self.combine4(nw, ne, sw, se)
+        } else {
+            let se = self.set_alive_in(se, level - 1, x - half, y - half);
+            self.combine4(nw, ne, sw, se)
+        }
+    }
+
+    /// Returns the center `2^(level-1) x 2^(level-1)` region of `node`
+    /// (level `k = node.level()`, `k >= 2`) advanced `2^(k-2)` generations,
+    /// memoized by node identity so recurring subtrees are only simulated once.
+    fn result(&mut self, node: Arc<Node>) -> Arc<Node> {
+        if let Some(cached) = self.results.get(&NodeRef(node.clone())) {
+            return cached.clone();
+        }
+
+        let level = node.level();
+        let result = if level == 2 {
+            self.base_case(&node)
+        } else {
+            let (nw, ne, sw, se) = node.children();
+            // The outer-corner grandchildren (`_nw_nw`, `_ne_ne`, `_sw_sw`, `_se_se`) aren't
+            // needed here: the corner blocks below (`m00`, `m02`, `m20`, `m22`) reuse the
+            // whole child (`nw`, `ne`, `sw`, `se`) directly instead of recombining grandchildren.
+            let (_nw_nw, nw_ne, nw_sw, nw_se) = nw.children();
+            let (ne_nw, _ne_ne, ne_sw, ne_se) = ne.children();
+            let (sw_nw, sw_ne, _sw_sw, sw_se) = sw.children();
+            let (se_nw, se_ne, se_sw, _se_se) = se.children();
+
+            // Nine overlapping level-(k-1) blocks covering `node`'s area on a half-size grid.
+            let m00 = nw;
+            let m01 = self.combine4(nw_ne, ne_nw, nw_se.clone(), ne_sw.clone());
+            let m02 = ne;
+            let m10 = self.combine4(nw_sw, nw_se.clone(), sw_nw, sw_ne.clone());
+            let m11 = self.combine4(nw_se, ne_sw.clone(), sw_ne.clone(), se_nw.clone());
+            let m12 = self.combine4(ne_sw, ne_se, se_nw.clone(), se_ne);
+            let m20 = sw;
+            let m21 = self.combine4(sw_ne, se_nw, sw_se, se_sw);
+            let m22 = se;
+
+            // First pass: advance each overlapping block by its own half-step.
+            let r00 = self.result(m00.clone());
+            let r01 = self.result(m01.clone());
+            let r02 = self.result(m02.clone());
+            let r10 = self.result(m10.clone());
+            let r11 = self.result(m11.clone());
+            let r12 = self.result(m12.clone());
+            let r20 = self.result(m20.clone());
+            let r21 = self.result(m21.clone());
+            let r22 = self.result(m22.clone());
+
+            // Second pass: combine the now-advanced quadrants and advance once more.
+            let a = self.combine4(r00, r01.clone(), r10.clone(), r11.clone());
+            let b = self.combine4(r01, r02, r11.clone(), r12.clone());
+            let c = self.combine4(r10, r11.clone(), r20, r21.clone());
+            let d = self.combine4(r11, r12, r21, r22);
+
+            let ra = self.result(a);
+            let rb = self.result(b);
+            let rc = self.result(c);
+            let rd = self.result(d);
+            self.combine4(ra, rb, rc, rd)
+        };
+
+        self.results.insert(NodeRef(node), result.clone());
+        result
+    }
+
+    /// Applies the B3/S23 rule directly to a level-2 (4x4) node's bits,
+    /// returning the center 2x2 advanced by one generation.
+    fn base_case(&mut self, node: &Arc<Node>) -> Arc<Node> {
+        let (nw, ne, sw, se) = node.children();
+        let nw_bits = match nw.as_ref() { Node::Leaf { bits } => *bits, _ => unreachable!() };
+        let ne_bits = match ne.as_ref() { Node::Leaf { bits } => *bits, _ => unreachable!() };
+        let sw_bits = match sw.as_ref() { Node::Leaf { bits } => *bits, _ => unreachable!() };
+        let se_bits = match se.as_ref() { Node::Leaf { bits } => *bits, _ => unreachable!() };
+
+        // Lay the 4x4 out as `grid[y][x]`, x/y in 0..4.
+        let mut grid = [[false; 4]; 4];
+        for (quadrant, ox, oy) in [(nw_bits, 0, 0), (ne_bits, 2, 0), (sw_bits, 0, 2), (se_bits, 2, 2)] {
+            for i in 0..4u8 {
+                if quadrant & (1 << i) != 0 {
+                    let (dx, dy) = (i % 2, i / 2);
+                    grid[(oy + dy) as usize][(ox + dx) as usize] = true;
+                }
+            }
+        }
+
+        let alive = |x: i32, y: i32| -> bool {
+            if x < 0 || y < 0 || x >= 4 || y >= 4 { false } else { grid[y as usize][x as usize] }
+        };
+
+        // The center 2x2 (x, y in 1..3) is the only region with full neighbor context.
+        let mut bits = 0u8;
+        for (i, (dx, dy)) in [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().enumerate() {
+            let (x, y) = (1 + dx, 1 + dy);
+            let neighbors = crate::DIRS.iter().filter(|(ddx, ddy)| alive(x + ddx, y + ddy)).count();
+            let next_alive = neighbors == 3 || (neighbors == 2 && alive(x, y));
+            if next_alive {
+                bits |= 1 << i;
+            }
+        }
+        self.make_leaf(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(positions: &[(i32, i32)]) -> std::collections::HashMap<Position, u32> {
+        positions.iter().map(|&(x, y)| (Position { x, y }, 1)).collect()
+    }
+
+    /// A glider never dies under B3/S23, so `step()` (however many generations it
+    /// actually advances) must always hand back exactly 5 live cells. This is the
+    /// invariant that would have caught the use-after-move bug in `result()` that
+    /// slipped through the crate's (then total) lack of tests.
+    #[test]
+    fn glider_population_is_conserved_across_steps() {
+        let mut engine = HashlifeGrid::from_cells(&cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]));
+        for _ in 0..4 {
+            engine.step();
+            assert_eq!(engine.to_cells().len(), 5);
+        }
+    }
+
+    /// A still life (block) is a fixed point of the rule, so any number of steps
+    /// must reproduce the exact same set of live cells at the same world
+    /// coordinates, even though the quadtree's origin shifts underneath it.
+    #[test]
+    fn block_still_life_is_unchanged_by_steps() {
+        let start = cells(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        let mut engine = HashlifeGrid::from_cells(&start);
+        engine.step();
+        let after: std::collections::HashSet<(i32, i32)> =
+            engine.to_cells().keys().map(|p| (p.x, p.y)).collect();
+        let mut expected: Vec<(i32, i32)> = start.keys().map(|p| (p.x, p.y)).collect();
+        expected.sort();
+        let mut actual: Vec<(i32, i32)> = after.into_iter().collect();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    /// Two subtrees with identical content, built independently within the same
+    /// engine, must canonicalize to the same allocation — the precondition the
+    /// results cache's pointer-identity keying depends on.
+    #[test]
+    fn identical_subtrees_hashcons_to_the_same_allocation() {
+        let mut engine = HashlifeGrid::from_cells(&cells(&[]));
+        let leaf_a = engine.make_leaf(0b1001);
+        let leaf_b = engine.make_leaf(0b1001);
+        assert!(Arc::ptr_eq(&leaf_a, &leaf_b));
+
+        let a = engine.combine4(leaf_a.clone(), leaf_a.clone(), leaf_a.clone(), leaf_a.clone());
+        let b = engine.combine4(leaf_b.clone(), leaf_b.clone(), leaf_b.clone(), leaf_b.clone());
+        assert!(Arc::ptr_eq(&a, &b));
+    }
+}